@@ -3,24 +3,397 @@ use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path;
 use std::path::PathBuf;
 use std::fs;
+use std::sync::OnceLock;
 
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
 
-type KeyIdx = collections::HashMap<Vec<u8>, (u64, u32)>;
+use argon2::Argon2;
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+type KeyIdx = collections::HashMap<Vec<u8>, (u64, u32, u8)>;
+// Same (val_pos, val_len, codec_id) entries as `KeyIdx`, kept in key order so `scan`/`prefix_scan`
+// can walk a range without sorting the whole keyspace on every call.
+type OrderedIdx = collections::BTreeMap<Vec<u8>, (u64, u32, u8)>;
 
 pub type Result<T> = std::result::Result<T, std::io::Error>;
 
 const KEY_VAL_COLUMN_LEN: u8 = 4;
 
+const CRC_LEN: u8 = 4;
+
+const CODEC_ID_LEN: u8 = 1;
+
+const ENC_MAGIC: &[u8; 4] = b"MBC1";
+const ENC_SALT_LEN: usize = 16;
+const ENC_NONCE_LEN: usize = 12;
+const ENC_TAG_LEN: usize = 16;
+const ENC_KEY_LEN: usize = 32;
+const ENC_CANARY: &[u8] = b"mini-bitcask-canary";
+
 const MERGE_FILE_TEMP_EXT: &str = "merge_ext";
+const CHUNK_LOG_EXT: &str = "chunks";
+const CHUNK_MERGE_FILE_TEMP_EXT: &str = "chunks_merge_ext";
+
+const CHUNK_HASH_LEN: usize = 32; // blake3 digest length
+
+const DEFAULT_MIN_CHUNK_SIZE: usize = 2 * 1024;
+const DEFAULT_AVG_CHUNK_SIZE: usize = 8 * 1024;
+const DEFAULT_MAX_CHUNK_SIZE: usize = 64 * 1024;
+
+// Castagnoli (CRC-32C) reversed polynomial, same variant used by iSCSI/ext4 metadata checksums.
+const CRC32C_POLY: u32 = 0x82f6_3b78;
+
+fn crc32c_table() -> &'static [u32; 256] {
+    static TABLE: OnceLock<[u32; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0u32; 256];
+        for (i, slot) in table.iter_mut().enumerate() {
+            let mut crc = i as u32;
+            for _ in 0..8 {
+                crc = if crc & 1 != 0 {
+                    (crc >> 1) ^ CRC32C_POLY
+                } else {
+                    crc >> 1
+                };
+            }
+            *slot = crc;
+        }
+        table
+    })
+}
+
+fn crc32c(data: &[u8]) -> u32 {
+    let table = crc32c_table();
+    let mut crc = !0u32;
+    for &byte in data {
+        crc = table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+/// Transparently encodes/decodes the value bytes stored in a record. The codec id a value was
+/// written with is persisted alongside it, so `merge` can recompress with whatever codec is
+/// currently configured without breaking records written under an older one.
+trait Compressor {
+    fn id(&self) -> u8;
+    fn compress(&self, data: &[u8]) -> Vec<u8>;
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>>;
+}
+
+struct NoneCompressor;
+
+impl Compressor for NoneCompressor {
+    fn id(&self) -> u8 {
+        0
+    }
+
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        data.to_vec()
+    }
+
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        Ok(data.to_vec())
+    }
+}
+
+struct ZlibCompressor;
+
+impl Compressor for ZlibCompressor {
+    fn id(&self) -> u8 {
+        1
+    }
+
+    fn compress(&self, data: &[u8]) -> Vec<u8> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).expect("in-memory zlib encode cannot fail");
+        encoder.finish().expect("in-memory zlib encode cannot fail")
+    }
+
+    fn decompress(&self, data: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        ZlibDecoder::new(data).read_to_end(&mut out)?;
+        Ok(out)
+    }
+}
+
+fn compressor_for(codec_id: u8) -> Result<Box<dyn Compressor>> {
+    match codec_id {
+        0 => Ok(Box::new(NoneCompressor)),
+        1 => Ok(Box::new(ZlibCompressor)),
+        id => Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("unknown compression codec id {}", id),
+        )),
+    }
+}
+
+/// AEAD algorithm used to seal values on disk when encryption-at-rest is enabled.
+#[derive(Clone, Copy)]
+enum AeadAlgo {
+    Aes256Gcm,
+    ChaCha20Poly1305,
+}
+
+impl AeadAlgo {
+    fn id(self) -> u8 {
+        match self {
+            AeadAlgo::Aes256Gcm => 0,
+            AeadAlgo::ChaCha20Poly1305 => 1,
+        }
+    }
+
+    fn from_id(id: u8) -> Result<Self> {
+        match id {
+            0 => Ok(AeadAlgo::Aes256Gcm),
+            1 => Ok(AeadAlgo::ChaCha20Poly1305),
+            id => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unknown AEAD algorithm id {}", id),
+            )),
+        }
+    }
+
+    fn seal(self, key: &[u8; ENC_KEY_LEN], nonce: &[u8; ENC_NONCE_LEN], plaintext: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            AeadAlgo::Aes256Gcm => {
+                use aes_gcm::aead::{Aead, KeyInit};
+                let cipher = aes_gcm::Aes256Gcm::new_from_slice(key)
+                    .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid AES-256-GCM key"))?;
+                cipher
+                    .encrypt(aes_gcm::Nonce::from_slice(nonce), plaintext)
+                    .map_err(|_| std::io::Error::other("AES-256-GCM encryption failed"))
+            }
+            AeadAlgo::ChaCha20Poly1305 => {
+                use chacha20poly1305::aead::{Aead, KeyInit};
+                let cipher = chacha20poly1305::ChaCha20Poly1305::new_from_slice(key).map_err(|_| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid ChaCha20-Poly1305 key")
+                })?;
+                cipher
+                    .encrypt(chacha20poly1305::Nonce::from_slice(nonce), plaintext)
+                    .map_err(|_| std::io::Error::other("ChaCha20-Poly1305 encryption failed"))
+            }
+        }
+    }
+
+    fn open(self, key: &[u8; ENC_KEY_LEN], nonce: &[u8; ENC_NONCE_LEN], ciphertext: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            AeadAlgo::Aes256Gcm => {
+                use aes_gcm::aead::{Aead, KeyInit};
+                let cipher = aes_gcm::Aes256Gcm::new_from_slice(key)
+                    .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid AES-256-GCM key"))?;
+                cipher
+                    .decrypt(aes_gcm::Nonce::from_slice(nonce), ciphertext)
+                    .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "AES-256-GCM authentication failed"))
+            }
+            AeadAlgo::ChaCha20Poly1305 => {
+                use chacha20poly1305::aead::{Aead, KeyInit};
+                let cipher = chacha20poly1305::ChaCha20Poly1305::new_from_slice(key).map_err(|_| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid ChaCha20-Poly1305 key")
+                })?;
+                cipher.decrypt(chacha20poly1305::Nonce::from_slice(nonce), ciphertext).map_err(|_| {
+                    std::io::Error::new(std::io::ErrorKind::InvalidData, "ChaCha20-Poly1305 authentication failed")
+                })
+            }
+        }
+    }
+}
+
+/// Derives a 256-bit key from a caller passphrase via Argon2id, salted per-store so the same
+/// passphrase never yields the same key across two different `Log` files.
+fn derive_key(passphrase: &str, salt: &[u8; ENC_SALT_LEN]) -> Result<[u8; ENC_KEY_LEN]> {
+    let mut key = [0u8; ENC_KEY_LEN];
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidInput, "argon2id key derivation failed"))?;
+    Ok(key)
+}
+
+/// In-memory state for an encrypted `Log`: the derived key plus the salt/algorithm it was
+/// derived with, so a `merge` can spin up a fresh file under the same key without re-prompting
+/// for the passphrase.
+#[derive(Clone, Copy)]
+struct EncryptionState {
+    key: [u8; ENC_KEY_LEN],
+    salt: [u8; ENC_SALT_LEN],
+    algo: AeadAlgo,
+}
+
+/// The Gear table backing FastCDC's rolling fingerprint: 256 fixed pseudo-random 64-bit
+/// constants, one per input byte value. Generated once from a fixed seed rather than drawn from
+/// an RNG, so the same input always cuts at the same boundaries across runs and versions.
+fn gear_table() -> &'static [u64; 256] {
+    static TABLE: OnceLock<[u64; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = [0u64; 256];
+        let mut seed = 0x9E37_79B9_7F4A_7C15u64;
+        for slot in table.iter_mut() {
+            // splitmix64
+            seed = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            *slot = z ^ (z >> 31);
+        }
+        table
+    })
+}
+
+/// Normalized-chunking parameters for FastCDC: cut as soon as the rolling fingerprint satisfies
+/// `mask_s` once past `min_size`, switching to the looser `mask_l` once past `avg_size`, and
+/// force a cut at `max_size` regardless. `avg_size` must be a power of two.
+struct FastCdcParams {
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    mask_s: u64,
+    mask_l: u64,
+}
+
+impl FastCdcParams {
+    fn new(min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        let bits = avg_size.trailing_zeros();
+        let mask_s = (1u64 << (bits + 1)) - 1;
+        let mask_l = (1u64 << bits.saturating_sub(1)) - 1;
+        Self { min_size, avg_size, max_size, mask_s, mask_l }
+    }
+}
+
+impl Default for FastCdcParams {
+    fn default() -> Self {
+        Self::new(DEFAULT_MIN_CHUNK_SIZE, DEFAULT_AVG_CHUNK_SIZE, DEFAULT_MAX_CHUNK_SIZE)
+    }
+}
+
+/// Splits `data` into content-defined chunks: same content, same boundaries, regardless of
+/// where it shifted inside a larger value, which is what lets equal chunks dedupe.
+fn fastcdc_chunks<'a>(data: &'a [u8], params: &FastCdcParams) -> Vec<&'a [u8]> {
+    let gear = gear_table();
+    let mut chunks = Vec::new();
+    let mut start = 0usize;
+
+    while start < data.len() {
+        let remaining = data.len() - start;
+        if remaining <= params.min_size {
+            chunks.push(&data[start..]);
+            break;
+        }
+
+        let max_len = remaining.min(params.max_size);
+        let mut fp: u64 = 0;
+        let mut i = params.min_size;
+        let mut cut = max_len;
+        while i < max_len {
+            fp = (fp << 1).wrapping_add(gear[data[start + i] as usize]);
+            let mask = if i < params.avg_size { params.mask_s } else { params.mask_l };
+            if fp & mask == 0 {
+                cut = i;
+                break;
+            }
+            i += 1;
+        }
+
+        chunks.push(&data[start..start + cut]);
+        start += cut;
+    }
+
+    chunks
+}
+
+/// A content-addressed, deduplicating value store: values are split into FastCDC chunks, each
+/// chunk is written to its own append-only `Log` keyed by its blake3 hash, and `set` stores a
+/// manifest of chunk hashes in place of the raw value. Chunk liveness isn't tracked
+/// incrementally — `merge` recomputes it from whichever manifests are still live, so a crash
+/// between writes can never leave a chunk's reference count stuck above (or below) zero.
+struct ChunkStore {
+    log: Log,
+    chunks: collections::HashMap<[u8; CHUNK_HASH_LEN], (u64, u32, u8)>,
+    params: FastCdcParams,
+}
+
+impl ChunkStore {
+    fn put(&mut self, val: &[u8]) -> Result<Vec<u8>> {
+        let mut manifest = Vec::with_capacity(CHUNK_HASH_LEN * (val.len() / self.params.avg_size + 1));
+
+        for chunk in fastcdc_chunks(val, &self.params) {
+            let hash = *blake3::hash(chunk).as_bytes();
+            manifest.extend_from_slice(&hash);
+
+            if !self.chunks.contains_key(&hash) {
+                let (val_pos, val_len) = self.log.write_one_entry(&hash, Some(chunk), NoneCompressor.id())?;
+                self.chunks.insert(hash, (val_pos, val_len, NoneCompressor.id()));
+            }
+        }
+
+        Ok(manifest)
+    }
+
+    fn assemble(&mut self, manifest: &[u8]) -> Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(manifest.len() / CHUNK_HASH_LEN * self.params.avg_size);
+
+        for hash in manifest.chunks(CHUNK_HASH_LEN) {
+            let hash: [u8; CHUNK_HASH_LEN] = hash
+                .try_into()
+                .map_err(|_| std::io::Error::new(std::io::ErrorKind::InvalidData, "malformed chunk manifest"))?;
+            let &(val_pos, val_len, _codec_id) = self.chunks.get(&hash).ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::NotFound, "referenced chunk missing from chunk store")
+            })?;
+            out.extend_from_slice(&self.log.read_value(val_pos, val_len)?);
+        }
+
+        Ok(out)
+    }
+
+    /// Rewrites the chunk log keeping only chunks referenced by `live_manifests`, dropping the
+    /// rest. Called from `merge` once the surviving set of manifests is known.
+    fn retain_only<'a>(&mut self, live_manifests: impl Iterator<Item = &'a [u8]>) -> Result<()> {
+        let mut live = collections::HashSet::new();
+        for manifest in live_manifests {
+            for hash in manifest.chunks(CHUNK_HASH_LEN) {
+                if let Ok(hash) = <[u8; CHUNK_HASH_LEN]>::try_from(hash) {
+                    live.insert(hash);
+                }
+            }
+        }
+
+        let mut new_chunk_path = self.log.path.clone();
+        new_chunk_path.set_extension(CHUNK_MERGE_FILE_TEMP_EXT);
+        let mut new_chunk_log = Log::new_for_merge(new_chunk_path, None)?;
+        let mut new_chunks = collections::HashMap::new();
+
+        for (&hash, &(val_pos, val_len, codec_id)) in self.chunks.iter() {
+            if !live.contains(&hash) {
+                continue;
+            }
+            let data = self.log.read_value(val_pos, val_len)?;
+            let (val_pos, val_len) = new_chunk_log.write_one_entry(&hash, Some(&data), codec_id)?;
+            new_chunks.insert(hash, (val_pos, val_len, codec_id));
+        }
+
+        std::fs::rename(&new_chunk_log.path, &self.log.path)?;
+        new_chunk_log.path = self.log.path.clone();
+        self.log = new_chunk_log;
+        self.chunks = new_chunks;
+
+        Ok(())
+    }
+}
 
 
 struct MiniBitcask {
     key_idx: KeyIdx, // key index in memory
+    ordered_idx: OrderedIdx, // same entries as key_idx, kept sorted for scan/prefix_scan
     log: Log,
+    compressor: Box<dyn Compressor>,
+    chunk_store: Option<ChunkStore>,
 }
 struct Log {
    file: std::fs::File,
    path: path::PathBuf,
+   header_len: u64, // bytes reserved at the front of the file for the encryption header, 0 if unencrypted
+   encryption: Option<EncryptionState>,
 }
 
 impl Drop for MiniBitcask {
@@ -33,23 +406,83 @@ impl Drop for MiniBitcask {
 
 impl MiniBitcask {
     fn new(path: PathBuf) -> Result<Self>{
-       
+       Self::new_with_compressor(path, Box::new(NoneCompressor))
+    }
+
+    fn new_with_compressor(path: PathBuf, compressor: Box<dyn Compressor>) -> Result<Self> {
        let log = Log::new(path)?;
 
        let key_idx = log.load_memory()?;
+       let ordered_idx: OrderedIdx = key_idx.iter().map(|(k, v)| (k.clone(), *v)).collect();
+
+       Ok(Self { log, key_idx, ordered_idx, compressor, chunk_store: None })
+    }
+
+    /// Opens (or creates) an encrypted store. On first creation a random salt is written into
+    /// the `Log` header and the key is derived from `passphrase` via Argon2id; on reopen the
+    /// stored salt is reused and a wrong passphrase fails here instead of surfacing as garbage
+    /// values later.
+    fn new_encrypted(path: PathBuf, passphrase: &str, algo: AeadAlgo) -> Result<Self> {
+       let log = Log::new_encrypted(path, passphrase, algo)?;
+
+       let key_idx = log.load_memory()?;
+       let ordered_idx: OrderedIdx = key_idx.iter().map(|(k, v)| (k.clone(), *v)).collect();
+
+       Ok(Self { log, key_idx, ordered_idx, compressor: Box::new(NoneCompressor), chunk_store: None })
+    }
 
-       Ok(Self { log, key_idx })    
+    /// Opens (or creates) a deduplicating store: values are content-defined-chunked and each
+    /// unique chunk is written once to a sibling `<path>.chunks` log, keyed by its blake3 hash.
+    fn new_deduped(path: PathBuf) -> Result<Self> {
+       Self::new_deduped_with_params(path, FastCdcParams::default())
+    }
+
+    fn new_deduped_with_params(path: PathBuf, params: FastCdcParams) -> Result<Self> {
+       let log = Log::new(path.clone())?;
+       let key_idx = log.load_memory()?;
+       let ordered_idx: OrderedIdx = key_idx.iter().map(|(k, v)| (k.clone(), *v)).collect();
+
+       let mut chunk_path = path;
+       chunk_path.set_extension(CHUNK_LOG_EXT);
+       let chunk_log = Log::new(chunk_path)?;
+       let chunks = chunk_log
+            .load_memory()?
+            .into_iter()
+            .filter_map(|(hash, (val_pos, val_len, codec_id))| {
+                let hash = <[u8; CHUNK_HASH_LEN]>::try_from(hash).ok()?;
+                Some((hash, (val_pos, val_len, codec_id)))
+            })
+            .collect();
+
+       Ok(Self {
+            log,
+            key_idx,
+            ordered_idx,
+            compressor: Box::new(NoneCompressor),
+            chunk_store: Some(ChunkStore { log: chunk_log, chunks, params }),
+       })
     }
 
     fn set(&mut self, key: &[u8], val: Vec<u8>) -> Result<()>{
-         let (val_pos, val_len) =  self.log.write_one_entry(key, Some(&val))?;
-         self.key_idx.insert(key.to_vec(), (val_pos , val_len));
+         let val = match &mut self.chunk_store {
+            Some(store) => store.put(&val)?,
+            None => val,
+         };
+         let compressed = self.compressor.compress(&val);
+         let (val_pos, val_len) =  self.log.write_one_entry(key, Some(&compressed), self.compressor.id())?;
+         self.key_idx.insert(key.to_vec(), (val_pos , val_len, self.compressor.id()));
+         self.ordered_idx.insert(key.to_vec(), (val_pos, val_len, self.compressor.id()));
          Ok(())
     }
 
     fn get(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>> {
-       if let Some((val_pos,val_len )) = self.key_idx.get(key) {
-            let val = self.log.read_value(*val_pos, *val_len)?;
+       if let Some(&(val_pos, val_len, codec_id)) = self.key_idx.get(key) {
+            let raw = self.log.read_value(val_pos, val_len)?;
+            let val = compressor_for(codec_id)?.decompress(&raw)?;
+            let val = match &mut self.chunk_store {
+                Some(store) => store.assemble(&val)?,
+                None => val,
+            };
             Ok(Some(val))
        }else {
             Ok(None)
@@ -57,106 +490,324 @@ impl MiniBitcask {
 
     }
 
+    /// Returns every live key/value pair whose key falls within `range`, in ascending key order.
+    /// The matching keys are snapshotted from `ordered_idx` up front, but each value is only read
+    /// (through the regular `get` path, so it benefits from compression/encryption/dedup handling)
+    /// as the returned iterator is advanced. A key whose value fails to decode surfaces as an
+    /// `Err` item for that key alone; the iterator carries on to the remaining keys rather than
+    /// discarding everything already produced.
+    fn scan<R: std::ops::RangeBounds<Vec<u8>>>(&mut self, range: R) -> ScanIter<'_> {
+        let keys = self.ordered_idx.range(range).map(|(k, _)| k.clone()).collect();
+        ScanIter { mini_bitcask: self, keys }
+    }
+
+    /// Returns every live key/value pair whose key starts with `prefix`, in ascending key order.
+    fn prefix_scan(&mut self, prefix: &[u8]) -> ScanIter<'_> {
+        let start = prefix.to_vec();
+        let mut upper = prefix.to_vec();
+        let end = loop {
+            match upper.last_mut() {
+                Some(last) if *last == u8::MAX => {
+                    upper.pop();
+                }
+                Some(last) => {
+                    *last += 1;
+                    break std::ops::Bound::Excluded(upper);
+                }
+                None => break std::ops::Bound::Unbounded,
+            }
+        };
+        self.scan((std::ops::Bound::Included(start), end))
+    }
+
     fn delete(&mut self, key: &[u8])  -> Result<()>{
-        self.log.write_one_entry(key, None)?;
+        self.log.write_one_entry(key, None, self.compressor.id())?;
         self.key_idx.remove(key);
+        self.ordered_idx.remove(key);
         Ok(())
     }
 
 
     fn merge(&mut self)  -> Result<()>{
-        // remove deleted key val pair in file
+        // remove deleted key val pair in file, recompressing every surviving value with the
+        // currently configured codec
         let mut merge_path = self.log.path.clone();
         merge_path.set_extension(MERGE_FILE_TEMP_EXT);
 
-        let mut new_log = Log::new(merge_path)?;
+        let mut new_log = Log::new_for_merge(merge_path, self.log.encryption)?;
         let mut new_key_idx = KeyIdx::new();
+        let mut new_ordered_idx = OrderedIdx::new();
+
+        let mut live_values = Vec::with_capacity(self.key_idx.len());
+        for (key, &(val_pos, val_len, codec_id)) in self.key_idx.iter() {
+            let raw = self.log.read_value(val_pos, val_len)?;
+            let val = compressor_for(codec_id)?.decompress(&raw)?;
+            live_values.push((key.clone(), val));
+        }
+
+        // chunk liveness is derived from the manifests that are about to survive the merge, not
+        // tracked incrementally, so this must run before those manifests are rewritten below
+        if let Some(store) = &mut self.chunk_store {
+            store.retain_only(live_values.iter().map(|(_, val)| val.as_slice()))?;
+        }
 
-        for ( key, (val_pos, val_len)) in self.key_idx.iter() {
-            let val = self.log.read_value(*val_pos, *val_len)?;
-            let (val_pos, val_len) = new_log.write_one_entry(key, Some(&val))?;
-            new_key_idx.insert(key.to_vec(), (val_pos, val_len));
+        for (key, val) in live_values {
+            let recompressed = self.compressor.compress(&val);
+            let (val_pos, val_len) = new_log.write_one_entry(&key, Some(&recompressed), self.compressor.id())?;
+            new_key_idx.insert(key.clone(), (val_pos, val_len, self.compressor.id()));
+            new_ordered_idx.insert(key, (val_pos, val_len, self.compressor.id()));
         }
-    
+
         std::fs::rename(&new_log.path, &self.log.path)?;
 
         self.log = new_log;
         self.key_idx = new_key_idx;
+        self.ordered_idx = new_ordered_idx;
 
         Ok(())
     }
 
 
     fn flush(&mut self) -> Result<()> {
-        Ok(self.log.file.sync_all()?)
+        self.log.file.sync_all()?;
+        if let Some(store) = &self.chunk_store {
+            store.log.file.sync_all()?;
+        }
+        Ok(())
+    }
+
+}
+
+/// Lazily resolves the keys snapshotted by `scan`/`prefix_scan` into values, one `get` call per
+/// `next()`. A decode failure on one key yields `Some(Err(_))` for that key only; the remaining
+/// keys are still produced on subsequent calls.
+struct ScanIter<'a> {
+    mini_bitcask: &'a mut MiniBitcask,
+    keys: collections::VecDeque<Vec<u8>>,
+}
+
+impl Iterator for ScanIter<'_> {
+    type Item = Result<(Vec<u8>, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(key) = self.keys.pop_front() {
+            match self.mini_bitcask.get(&key) {
+                Ok(Some(val)) => return Some(Ok((key, val))),
+                Ok(None) => continue, // deleted since the range was snapshotted
+                Err(err) => return Some(Err(err)),
+            }
+        }
+        None
     }
-   
 }
 
 impl Log {
     fn new(path: PathBuf) -> Result<Self>{
+        Self::new_merge_target(path, None)
+    }
+
+    /// Opens `path` as a fresh merge-target `Log`, carrying over `encryption` from the log being
+    /// compacted. Any stale file left behind by a merge that crashed before its final `rename`
+    /// is removed first, so a retried merge never reopens (and appends after) leftover garbage.
+    fn new_for_merge(path: PathBuf, encryption: Option<EncryptionState>) -> Result<Self> {
+        fs::remove_file(&path).ok();
+        Self::new_merge_target(path, encryption)
+    }
+
+    /// Opens `path` read/write, creating it (and its parent directories) if it doesn't exist yet.
+    /// Never truncates, so the caller is always responsible for clearing `path` first if it needs
+    /// a fresh file (see `new_for_merge`).
+    fn open_rw(path: &path::Path) -> Result<std::fs::File> {
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)?;
         }
 
-        let file = std::fs::OpenOptions::new()
-                        .read(true)
-                        .write(true)
-                        .create(true)
-                        .open(path.as_path())?;
-        let path = path.clone();
+        std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+    }
+
+    /// Opens `path` as a `Log`. With `encryption: None` any existing content is preserved, so this
+    /// is safe to use for reopening a live log across restarts (`Log::new`). With `Some(_)` the
+    /// encryption header is written at offset 0 unconditionally, over whatever is already there,
+    /// so that case is only used on a freshly cleared file (see `new_for_merge`).
+    fn new_merge_target(path: PathBuf, encryption: Option<EncryptionState>) -> Result<Self> {
+        let mut file = Self::open_rw(&path)?;
+
+        let (header_len, encryption) = match encryption {
+            Some(enc) => {
+                let (header_len, state) = Self::write_header_with_key(&mut file, enc.salt, enc.key, enc.algo)?;
+                (header_len, Some(state))
+            }
+            None => (0, None),
+        };
+
+        Ok(Self { file, path, header_len, encryption })
+    }
+
+    /// Opens (or creates) an encrypted `Log`. A fresh file gets a random salt and a canary value
+    /// sealed under the derived key; a reopened file reads that salt back and re-derives the key,
+    /// then unseals the canary to confirm `passphrase` is correct before any real record is read.
+    fn new_encrypted(path: PathBuf, passphrase: &str, algo: AeadAlgo) -> Result<Self> {
+        let mut file = Self::open_rw(&path)?;
+
+        let (header_len, encryption) = if file.metadata()?.len() == 0 {
+            Self::write_encryption_header(&mut file, passphrase, algo)?
+        } else {
+            Self::read_encryption_header(&mut file, passphrase)?
+        };
+
+        Ok(Self { file, path, header_len, encryption: Some(encryption) })
+    }
+
+    fn write_header_with_key(
+        file: &mut std::fs::File,
+        salt: [u8; ENC_SALT_LEN],
+        key: [u8; ENC_KEY_LEN],
+        algo: AeadAlgo,
+    ) -> Result<(u64, EncryptionState)> {
+        let mut canary_nonce = [0u8; ENC_NONCE_LEN];
+        OsRng.fill_bytes(&mut canary_nonce);
+        let canary_ct = algo.seal(&key, &canary_nonce, ENC_CANARY)?;
+
+        file.seek(SeekFrom::Start(0))?;
+        file.write_all(ENC_MAGIC)?;
+        file.write_all(&[algo.id()])?;
+        file.write_all(&salt)?;
+        file.write_all(&canary_nonce)?;
+        file.write_all(&canary_ct)?;
+        file.flush()?;
+
+        let header_len = ENC_MAGIC.len() as u64
+            + 1
+            + ENC_SALT_LEN as u64
+            + ENC_NONCE_LEN as u64
+            + canary_ct.len() as u64;
+        Ok((header_len, EncryptionState { key, salt, algo }))
+    }
+
+    fn write_encryption_header(
+        file: &mut std::fs::File,
+        passphrase: &str,
+        algo: AeadAlgo,
+    ) -> Result<(u64, EncryptionState)> {
+        let mut salt = [0u8; ENC_SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let key = derive_key(passphrase, &salt)?;
+        Self::write_header_with_key(file, salt, key, algo)
+    }
+
+    fn read_encryption_header(file: &mut std::fs::File, passphrase: &str) -> Result<(u64, EncryptionState)> {
+        file.seek(SeekFrom::Start(0))?;
+
+        let mut magic = [0u8; 4];
+        file.read_exact(&mut magic)?;
+        if &magic != ENC_MAGIC {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "missing encryption header"));
+        }
+
+        let mut algo_id = [0u8; 1];
+        file.read_exact(&mut algo_id)?;
+        let algo = AeadAlgo::from_id(algo_id[0])?;
+
+        let mut salt = [0u8; ENC_SALT_LEN];
+        file.read_exact(&mut salt)?;
+        let key = derive_key(passphrase, &salt)?;
+
+        let mut canary_nonce = [0u8; ENC_NONCE_LEN];
+        file.read_exact(&mut canary_nonce)?;
+        let mut canary_ct = vec![0u8; ENC_CANARY.len() + ENC_TAG_LEN];
+        file.read_exact(&mut canary_ct)?;
 
-        Ok(Self { file, path })
+        // a wrong passphrase derives a different key, so the canary fails to authenticate here
+        // instead of silently corrupting every value read afterwards
+        algo.open(&key, &canary_nonce, &canary_ct)?;
+
+        let header_len = 4 + 1 + ENC_SALT_LEN as u64 + ENC_NONCE_LEN as u64 + canary_ct.len() as u64;
+        Ok((header_len, EncryptionState { key, salt, algo }))
     }
 
- 
-    /// -------------------------------------------------------------------------——-
-    /// ｜     ksz (4byte)    |    value_sz (4byte)    |    key      |     valie    |
-    /// --------------------------------------------------------------------------——
-    /// 
-    /// 
-    /// 
+
+    /// -------------------------------------------------------------------------------——------------
+    /// ｜ ksz (4byte) | value_sz (4byte) | codec_id (1byte) |    key      |     valie    | crc (4byte) |
+    /// --------------------------------------------------------------------------------——------------
+    ///
+    /// the trailing crc32c covers ksz | value_sz | codec_id | key | value and lets a reboot tell
+    /// a clean record from a torn write. codec_id records which `Compressor` encoded the value so
+    /// `get`/`merge` can decode it even after the configured codec has since changed.
+    ///
     fn load_memory(&self) -> Result<KeyIdx> {
         let mut key_idx = KeyIdx::new();
         let mut len_buf = [0u8; KEY_VAL_COLUMN_LEN as usize];
+        let mut codec_buf = [0u8; CODEC_ID_LEN as usize];
+        let mut crc_buf = [0u8; CRC_LEN as usize];
         let file_len = self.file.metadata()?.len();
         let mut reader = BufReader::new(&self.file);
-        let mut pos = reader.seek(SeekFrom::Start(0))?;
+        let mut pos = reader.seek(SeekFrom::Start(self.header_len))?;
 
         while pos < file_len {
-            let one_enrty = || -> Result<(Vec<u8>, (u64, Option<u32>))> {
+            let one_enrty = || -> Result<(Vec<u8>, u64, Option<(u32, u8)>, u64)> {
                 reader.read_exact(&mut len_buf)?;
                 let ksz = u32::from_be_bytes(len_buf);
                 reader.read_exact(&mut len_buf)?;
-                let value_sz_r = match  u32::from_be_bytes(len_buf) {
-                    l if l >0 => Some(l),
-                    _ => None,
-                };
-                let value_pos = pos + KEY_VAL_COLUMN_LEN as u64 * 2 + ksz as u64;
-                
+                let value_sz = u32::from_be_bytes(len_buf);
+                reader.read_exact(&mut codec_buf)?;
+                let codec_id = codec_buf[0];
+                let value_pos = pos + KEY_VAL_COLUMN_LEN as u64 * 2 + CODEC_ID_LEN as u64 + ksz as u64;
+
                 let mut key = vec![0u8; ksz as usize];
                 reader.read_exact(&mut key)?;
-                
-                // Do not load value in memeory to save spaces
 
-                Ok((key, (value_pos, value_sz_r)))
+                let mut value = vec![0u8; value_sz as usize];
+                reader.read_exact(&mut value)?;
+
+                reader.read_exact(&mut crc_buf)?;
+                let expected_crc = u32::from_be_bytes(crc_buf);
+
+                let mut body = Vec::with_capacity(
+                    KEY_VAL_COLUMN_LEN as usize * 2 + CODEC_ID_LEN as usize + key.len() + value.len(),
+                );
+                body.extend_from_slice(&ksz.to_be_bytes());
+                body.extend_from_slice(&value_sz.to_be_bytes());
+                body.push(codec_id);
+                body.extend_from_slice(&key);
+                body.extend_from_slice(&value);
+
+                if crc32c(&body) != expected_crc {
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "crc32c mismatch"));
+                }
+
+                let entry_len = body.len() as u64 + CRC_LEN as u64;
+                let value_sz_r = match value_sz {
+                    l if l > 0 => Some((l, codec_id)),
+                    _ => None,
+                };
+
+                Ok((key, value_pos, value_sz_r, entry_len))
 
             }();
 
             match one_enrty  {
-                Ok((key, (v_pos, Some(v_sz)))) => {
-                    key_idx.insert(key, (v_pos, v_sz));
-                    //we do not read value from file to memory, so the pos need skip the value 
-                    reader.seek_relative(v_sz as i64)?;
-                    pos =  v_pos + v_sz as u64;
+                Ok((key, v_pos, Some((v_sz, codec_id)), entry_len)) => {
+                    key_idx.insert(key, (v_pos, v_sz, codec_id));
+                    pos += entry_len;
 
                 },
-                Ok((key, (v_pos, None))) => {
+                Ok((key, v_pos, None, entry_len)) => {
                      key_idx.remove(&key);
-                     pos =  v_pos ;
+                     let _ = v_pos;
+                     pos += entry_len;
 
                 },
-                Err(err) => return Err(err),
+                Err(_) => {
+                    // Torn write or bit-rot: stop replaying and drop the corrupt tail so the
+                    // log stays append-ready from the last known-good record.
+                    self.file.set_len(pos)?;
+                    break;
+                },
 
             }
 
@@ -165,29 +816,48 @@ impl Log {
         Ok(key_idx)
     }
 
-    fn write_one_entry(&mut self, key: &[u8], val: Option<&[u8]>) -> Result<(u64, u32)> {
+    fn write_one_entry(&mut self, key: &[u8], val: Option<&[u8]>, codec_id: u8) -> Result<(u64, u32)> {
         let key_len = key.len() as u32;
-        let val_len = val.map_or(0, |val| val.len()) as u32;
 
-        let entry_len =  KEY_VAL_COLUMN_LEN as usize * 2 + key_len as usize + val_len as usize;
+        // when encryption is enabled, seal the value under a fresh nonce before it ever touches
+        // disk; the stored "value" is then nonce | ciphertext | tag
+        let sealed_val: Option<Vec<u8>> = match (&self.encryption, val) {
+            (Some(enc), Some(plain)) => {
+                let mut nonce = [0u8; ENC_NONCE_LEN];
+                OsRng.fill_bytes(&mut nonce);
+                let ciphertext = enc.algo.seal(&enc.key, &nonce, plain)?;
+                let mut sealed = Vec::with_capacity(ENC_NONCE_LEN + ciphertext.len());
+                sealed.extend_from_slice(&nonce);
+                sealed.extend_from_slice(&ciphertext);
+                Some(sealed)
+            }
+            (None, Some(plain)) => Some(plain.to_vec()),
+            (_, None) => None,
+        };
+        let val_len = sealed_val.as_ref().map_or(0, |val| val.len()) as u32;
+
+        let mut body = Vec::with_capacity(
+            KEY_VAL_COLUMN_LEN as usize * 2 + CODEC_ID_LEN as usize + key_len as usize + val_len as usize,
+        );
+        body.extend_from_slice(&key_len.to_be_bytes());
+        body.extend_from_slice(&val_len.to_be_bytes());
+        body.push(codec_id);
+        body.extend_from_slice(key);
+        if let Some(val) = &sealed_val {
+            body.extend_from_slice(val);
+        }
+        let crc = crc32c(&body);
+
+        let entry_len = body.len() + CRC_LEN as usize;
 
         let offset = self.file.seek(SeekFrom::End(0)) ?;
         let mut writer = BufWriter::with_capacity(entry_len, &self.file);
 
-        writer.write_all(&key_len.to_be_bytes())?;
-        writer.write_all(&val_len.to_be_bytes())?;
-        writer.write_all(key)?;
-        match val_len {
-            l if l > 0 => {
-                writer.write_all(val.unwrap().as_ref())?;
-            },
-            _ => {
-                
-            }
-        };
+        writer.write_all(&body)?;
+        writer.write_all(&crc.to_be_bytes())?;
         writer.flush()?;
 
-        let val_pos = offset + entry_len as u64 - val_len as u64;
+        let val_pos = offset + entry_len as u64 - val_len as u64 - CRC_LEN as u64;
         Ok((val_pos, val_len as u32))
 
     }
@@ -197,9 +867,23 @@ impl Log {
         let mut val_buff = vec![0; val_len as usize];
         self.file.seek(SeekFrom::Start(val_pos))?;
         self.file.read_exact(&mut val_buff)?;
-        Ok(val_buff)
+
+        match &self.encryption {
+            Some(enc) => {
+                if val_buff.len() < ENC_NONCE_LEN {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "encrypted value shorter than a nonce",
+                    ));
+                }
+                let (nonce, ciphertext) = val_buff.split_at(ENC_NONCE_LEN);
+                let nonce: [u8; ENC_NONCE_LEN] = nonce.try_into().unwrap();
+                enc.algo.open(&enc.key, &nonce, ciphertext)
+            }
+            None => Ok(val_buff),
+        }
     }
-    
+
 }
 
 
@@ -276,13 +960,13 @@ mod tests {
         
         let mut len = mini_bitcask.log.file.metadata()?.len();
 
-        assert!(len == 19 + 27);
+        assert!(len == 24 + 32);
 
         mini_bitcask.merge()?;
 
         len = mini_bitcask.log.file.metadata()?.len();
 
-        assert!(len == 27);
+        assert!(len == 32);
 
         Ok(())
     }
@@ -315,4 +999,224 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_merge_ignores_stale_merge_target_left_by_a_crash() -> Result<()> {
+        let temp = std::env::temp_dir().join("bitcask_test11").join("log");
+        if temp.exists() {
+            fs::remove_file(temp.clone())?;
+        }
+        let mut mini_bitcask = MiniBitcask::new(temp.clone())?;
+        mini_bitcask.set(b"CQM", b"handsome".to_vec())?;
+
+        // simulate a merge that crashed after creating the merge-target file but before the
+        // final rename: leave garbage bytes sitting in `<path>.merge_ext`
+        let mut merge_path = temp.clone();
+        merge_path.set_extension(MERGE_FILE_TEMP_EXT);
+        fs::write(&merge_path, b"garbage left behind by a crashed merge")?;
+
+        mini_bitcask.merge()?;
+
+        assert_eq!(mini_bitcask.get(b"CQM")?.unwrap(), b"handsome".to_vec());
+
+        drop(mini_bitcask);
+        let mut reboot_bitcask = MiniBitcask::new(temp)?;
+        assert_eq!(reboot_bitcask.get(b"CQM")?.unwrap(), b"handsome".to_vec());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reboot_recovers_from_corrupt_tail() -> Result<()> {
+        let temp = std::env::temp_dir().join("bitcask_test06").join("log");
+        if temp.exists() {
+            fs::remove_file(temp.clone())?;
+        }
+        let mut mini_bitcask = MiniBitcask::new(temp.clone())?;
+        mini_bitcask.set(b"CQM", b"handsome".to_vec())?;
+        let good_len = mini_bitcask.log.file.metadata()?.len();
+
+        mini_bitcask.set(b"torn", b"write".to_vec())?;
+        drop(mini_bitcask);
+
+        // simulate a crash mid-append: truncate away the last few bytes of the second record
+        let file = fs::OpenOptions::new().write(true).open(&temp)?;
+        let full_len = file.metadata()?.len();
+        file.set_len(full_len - 3)?;
+        drop(file);
+
+        let mut reboot_bitcask = MiniBitcask::new(temp.clone())?;
+        assert_eq!(reboot_bitcask.get(b"CQM")?.unwrap(), b"handsome".to_vec());
+        assert_eq!(reboot_bitcask.get(b"torn")?, None);
+        assert_eq!(reboot_bitcask.log.file.metadata()?.len(), good_len);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zlib_compressor_set_get() -> Result<()> {
+        let temp = std::env::temp_dir().join("bitcask_test07").join("log");
+        if temp.exists() {
+            fs::remove_file(temp.clone())?;
+        }
+        let mut mini_bitcask = MiniBitcask::new_with_compressor(temp.clone(), Box::new(ZlibCompressor))?;
+        let val = b"handsome ".repeat(64);
+        mini_bitcask.set(b"CQM", val.clone())?;
+
+        assert_eq!(mini_bitcask.get(b"CQM")?.unwrap(), val);
+
+        drop(mini_bitcask);
+
+        let mut reboot_bitcask = MiniBitcask::new_with_compressor(temp.clone(), Box::new(ZlibCompressor))?;
+        assert_eq!(reboot_bitcask.get(b"CQM")?.unwrap(), val);
+
+        reboot_bitcask.merge()?;
+        assert_eq!(reboot_bitcask.get(b"CQM")?.unwrap(), val);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypted_set_get_reboot_and_wrong_passphrase() -> Result<()> {
+        let temp = std::env::temp_dir().join("bitcask_test08").join("log");
+        if temp.exists() {
+            fs::remove_file(temp.clone())?;
+        }
+        let mut mini_bitcask = MiniBitcask::new_encrypted(temp.clone(), "correct horse battery staple", AeadAlgo::Aes256Gcm)?;
+        mini_bitcask.set(b"CQM", b"handsome".to_vec())?;
+        assert_eq!(mini_bitcask.get(b"CQM")?.unwrap(), b"handsome".to_vec());
+        drop(mini_bitcask);
+
+        // the ciphertext on disk must not contain the plaintext value
+        let raw = fs::read(&temp)?;
+        assert!(!raw.windows(b"handsome".len()).any(|w| w == b"handsome"));
+
+        assert!(MiniBitcask::new_encrypted(temp.clone(), "wrong passphrase", AeadAlgo::Aes256Gcm).is_err());
+
+        let mut reboot_bitcask = MiniBitcask::new_encrypted(temp.clone(), "correct horse battery staple", AeadAlgo::Aes256Gcm)?;
+        assert_eq!(reboot_bitcask.get(b"CQM")?.unwrap(), b"handsome".to_vec());
+
+        reboot_bitcask.merge()?;
+        assert_eq!(reboot_bitcask.get(b"CQM")?.unwrap(), b"handsome".to_vec());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_default_params_set_get() -> Result<()> {
+        let temp = std::env::temp_dir().join("bitcask_test13").join("log");
+        if temp.exists() {
+            fs::remove_file(temp.clone())?;
+        }
+        let chunk_path = temp.with_extension(CHUNK_LOG_EXT);
+        if chunk_path.exists() {
+            fs::remove_file(chunk_path.clone())?;
+        }
+
+        let mut mini_bitcask = MiniBitcask::new_deduped(temp)?;
+        mini_bitcask.set(b"CQM", b"handsome".to_vec())?;
+        assert_eq!(mini_bitcask.get(b"CQM")?.unwrap(), b"handsome".to_vec());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_shares_chunks_across_keys_and_gcs_on_merge() -> Result<()> {
+        let temp = std::env::temp_dir().join("bitcask_test09").join("log");
+        if temp.exists() {
+            fs::remove_file(temp.clone())?;
+        }
+        let chunk_path = temp.with_extension(CHUNK_LOG_EXT);
+        if chunk_path.exists() {
+            fs::remove_file(chunk_path.clone())?;
+        }
+
+        let params = FastCdcParams::new(64, 256, 1024);
+        let mut mini_bitcask = MiniBitcask::new_deduped_with_params(temp.clone(), params)?;
+
+        let shared = b"the quick brown fox jumps over the lazy dog ".repeat(40);
+        mini_bitcask.set(b"a", shared.clone())?;
+        mini_bitcask.set(b"b", shared.clone())?;
+
+        let chunk_count_before = mini_bitcask.chunk_store.as_ref().unwrap().chunks.len();
+        assert!(chunk_count_before > 0);
+
+        assert_eq!(mini_bitcask.get(b"a")?.unwrap(), shared);
+        assert_eq!(mini_bitcask.get(b"b")?.unwrap(), shared);
+
+        mini_bitcask.delete(b"a")?;
+        mini_bitcask.merge()?;
+
+        assert_eq!(mini_bitcask.get(b"a")?, None);
+        assert_eq!(mini_bitcask.get(b"b")?.unwrap(), shared);
+        // "b" still references every chunk, so merge must not have GC'd any of them
+        assert_eq!(mini_bitcask.chunk_store.as_ref().unwrap().chunks.len(), chunk_count_before);
+
+        mini_bitcask.delete(b"b")?;
+        mini_bitcask.merge()?;
+        assert_eq!(mini_bitcask.chunk_store.as_ref().unwrap().chunks.len(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_merge_ignores_stale_chunk_merge_target_left_by_a_crash() -> Result<()> {
+        let temp = std::env::temp_dir().join("bitcask_test12").join("log");
+        if temp.exists() {
+            fs::remove_file(temp.clone())?;
+        }
+        let chunk_path = temp.with_extension(CHUNK_LOG_EXT);
+        if chunk_path.exists() {
+            fs::remove_file(chunk_path.clone())?;
+        }
+
+        let params = FastCdcParams::new(64, 256, 1024);
+        let mut mini_bitcask = MiniBitcask::new_deduped_with_params(temp.clone(), params)?;
+
+        let shared = b"the quick brown fox jumps over the lazy dog ".repeat(40);
+        mini_bitcask.set(b"a", shared.clone())?;
+
+        // simulate a merge that crashed after creating the chunk merge-target file but before
+        // the final rename: leave garbage bytes sitting in `<path>.chunks_merge_ext`
+        let mut chunk_merge_path = chunk_path.clone();
+        chunk_merge_path.set_extension(CHUNK_MERGE_FILE_TEMP_EXT);
+        fs::write(&chunk_merge_path, b"garbage left behind by a crashed merge")?;
+
+        mini_bitcask.merge()?;
+
+        assert_eq!(mini_bitcask.get(b"a")?.unwrap(), shared);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_and_prefix_scan_return_keys_in_order() -> Result<()> {
+        let temp = std::env::temp_dir().join("bitcask_test10").join("log");
+        if temp.exists() {
+            fs::remove_file(temp.clone())?;
+        }
+        let mut mini_bitcask = MiniBitcask::new(temp)?;
+
+        mini_bitcask.set(b"fruit/apple", b"1".to_vec())?;
+        mini_bitcask.set(b"fruit/banana", b"2".to_vec())?;
+        mini_bitcask.set(b"fruit/cherry", b"3".to_vec())?;
+        mini_bitcask.set(b"veggie/carrot", b"4".to_vec())?;
+        mini_bitcask.delete(b"fruit/banana")?;
+
+        let ranged: Vec<(Vec<u8>, Vec<u8>)> = mini_bitcask
+            .scan(b"fruit/apple".to_vec()..b"fruit/cherry".to_vec())
+            .collect::<Result<_>>()?;
+        assert_eq!(ranged, vec![(b"fruit/apple".to_vec(), b"1".to_vec())]);
+
+        let prefixed: Vec<(Vec<u8>, Vec<u8>)> = mini_bitcask.prefix_scan(b"fruit/").collect::<Result<_>>()?;
+        assert_eq!(
+            prefixed,
+            vec![
+                (b"fruit/apple".to_vec(), b"1".to_vec()),
+                (b"fruit/cherry".to_vec(), b"3".to_vec()),
+            ]
+        );
+
+        Ok(())
+    }
+
 }
\ No newline at end of file